@@ -8,22 +8,137 @@
 // Config comes in as arguments from the frontend (which reads it from the
 // Python backend's /api/settings endpoint at startup).
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+use tracing::{error, info, instrument, warn};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloudflareConfig {
     pub endpoint: String,
     pub api_token: String,
     pub rag_top_k: Option<u32>,
+    /// Backend to route requests through: `cloudflare` (default), `openai`
+    /// (OpenAI-compatible SSE), or `replicate` (async prediction polling).
+    #[serde(default)]
+    pub provider: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String,
+    #[serde(default)]
     pub content: String,
+    /// Set on `role: "tool"` messages to correlate a result with its call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Set on the assistant turn that requested tool calls (OpenAI format).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<serde_json::Value>>,
+}
+
+/// A tool the model is allowed to call, forwarded verbatim in the payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSpec {
+    pub name: String,
+    /// JSON-schema object describing the tool's parameters.
+    pub parameters: serde_json::Value,
+}
+
+/// A tool call accumulated from the streamed `tool_calls` deltas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// Raw JSON string of arguments, concatenated across delta fragments.
+    pub arguments: String,
+    /// `true` for `may_`-prefixed side-effecting tools that the frontend must
+    /// confirm with the user before executing.
+    pub needs_confirmation: bool,
+}
+
+/// Result of executing a single tool call, submitted back by the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolResult {
+    pub tool_call_id: String,
+    pub content: String,
+}
+
+/// Maximum number of tool-calling round trips before giving up, to bound runaway
+/// recursion.
+const MAX_TOOL_STEPS: usize = 8;
+
+/// Per-request cancellation flags for in-flight chat streams.
+///
+/// A lightweight `AtomicBool` is used rather than an `RwLock` so the hot
+/// streaming loop can poll it with a cheap relaxed load each iteration.
+#[derive(Default)]
+pub struct ChatCancelRegistry {
+    flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl ChatCancelRegistry {
+    fn register(&self, request_id: &str) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        self.flags
+            .lock()
+            .unwrap()
+            .insert(request_id.to_string(), flag.clone());
+        flag
+    }
+
+    fn cancel(&self, request_id: &str) -> bool {
+        match self.flags.lock().unwrap().get(request_id) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove(&self, request_id: &str) {
+        self.flags.lock().unwrap().remove(request_id);
+    }
+}
+
+/// Registry of in-flight tool-call turns awaiting results from the frontend.
+///
+/// When a turn finishes with `finish_reason: "tool_calls"` the command emits a
+/// `cloudflare-tool-call` event carrying a `turn_id` and parks a oneshot
+/// receiver here; `submit_tool_results` wakes it with the executed results.
+#[derive(Default)]
+pub struct ToolCallRegistry {
+    pending: Mutex<HashMap<u64, oneshot::Sender<Vec<ToolResult>>>>,
+    next_id: AtomicU64,
+}
+
+impl ToolCallRegistry {
+    fn register(&self) -> (u64, oneshot::Receiver<Vec<ToolResult>>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        (id, rx)
+    }
+
+    fn resolve(&self, turn_id: u64, results: Vec<ToolResult>) -> Result<(), String> {
+        let sender = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&turn_id)
+            .ok_or_else(|| format!("no pending tool-call turn {}", turn_id))?;
+        sender
+            .send(results)
+            .map_err(|_| "tool-call turn is no longer awaiting results".to_string())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,40 +148,579 @@ pub struct MemoryMatch {
     pub text: String,
 }
 
-fn build_client() -> Client {
+pub(crate) fn build_client() -> Client {
     Client::builder()
         .timeout(std::time::Duration::from_secs(60))
         .build()
         .unwrap_or_default()
 }
 
-fn auth_header(token: &str) -> String {
+pub(crate) fn auth_header(token: &str) -> String {
     format!("Bearer {}", token)
 }
 
+// ─────────────────────────── Provider abstraction ────────────────────────────
+
+/// A chat/memory backend. The three Tauri commands dispatch through the provider
+/// selected by `CloudflareConfig::provider`, so the same UI works against Workers
+/// AI, an OpenAI-compatible server, or asynchronous Replicate models.
+#[async_trait]
+pub trait Provider: Send + Sync {
+    async fn stream_chat(
+        &self,
+        app: &AppHandle,
+        registry: &ToolCallRegistry,
+        messages: Vec<ChatMessage>,
+        system: Option<String>,
+        eval_target: Option<String>,
+        tools: Vec<ToolSpec>,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<(), String>;
+
+    async fn ingest_memory(
+        &self,
+        text: String,
+        metadata: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, String>;
+
+    async fn search_memory(
+        &self,
+        query: String,
+        top_k: Option<u32>,
+    ) -> Result<Vec<MemoryMatch>, String>;
+}
+
+/// Select the provider implementation for `config`. Workers AI and
+/// OpenAI-compatible endpoints share the SSE client; Replicate uses polling.
+pub fn provider_for(config: CloudflareConfig) -> Box<dyn Provider> {
+    match config.provider.as_deref() {
+        Some("replicate") => Box::new(ReplicateProvider { config }),
+        _ => Box::new(CloudflareProvider { config }),
+    }
+}
+
+/// Workers AI / OpenAI-compatible provider: a single streaming SSE request
+/// (with tool-calling support) plus the Worker's memory endpoints.
+pub struct CloudflareProvider {
+    config: CloudflareConfig,
+}
+
+#[async_trait]
+impl Provider for CloudflareProvider {
+    async fn stream_chat(
+        &self,
+        app: &AppHandle,
+        registry: &ToolCallRegistry,
+        messages: Vec<ChatMessage>,
+        system: Option<String>,
+        eval_target: Option<String>,
+        tools: Vec<ToolSpec>,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<(), String> {
+        run_chat_turns(
+            app,
+            registry,
+            &self.config,
+            messages,
+            system,
+            eval_target,
+            tools,
+            cancel.as_ref(),
+        )
+        .await
+    }
+
+    async fn ingest_memory(
+        &self,
+        text: String,
+        metadata: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        let client = build_client();
+        let url = format!("{}/api/memory/ingest", self.config.endpoint.trim_end_matches('/'));
+
+        let mut payload = json!({ "text": text });
+        if let Some(m) = metadata {
+            payload["metadata"] = m;
+        }
+
+        let resp = client
+            .post(&url)
+            .header("Authorization", auth_header(&self.config.api_token))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+
+        resp.json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("JSON parse error: {}", e))
+    }
+
+    async fn search_memory(
+        &self,
+        query: String,
+        top_k: Option<u32>,
+    ) -> Result<Vec<MemoryMatch>, String> {
+        let client = build_client();
+        let url = format!("{}/api/memory/search", self.config.endpoint.trim_end_matches('/'));
+
+        let payload = json!({
+            "query": query,
+            "top_k": top_k.unwrap_or(self.config.rag_top_k.unwrap_or(5)),
+        });
+
+        let resp = client
+            .post(&url)
+            .header("Authorization", auth_header(&self.config.api_token))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+
+        let data: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("JSON parse error: {}", e))?;
+
+        Ok(parse_matches(&data))
+    }
+}
+
+/// Replicate-style provider: a `POST` creates a prediction, then its `urls.get`
+/// is polled (500ms→2s backoff) until `status` reaches `succeeded`/`failed`,
+/// emitting each new `output` element as a `cloudflare-token` event so the
+/// frontend stays uniform across providers.
+pub struct ReplicateProvider {
+    config: CloudflareConfig,
+}
+
+#[async_trait]
+impl Provider for ReplicateProvider {
+    async fn stream_chat(
+        &self,
+        app: &AppHandle,
+        _registry: &ToolCallRegistry,
+        messages: Vec<ChatMessage>,
+        system: Option<String>,
+        eval_target: Option<String>,
+        _tools: Vec<ToolSpec>,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<(), String> {
+        let client = build_client();
+        let url = self.config.endpoint.trim_end_matches('/').to_string();
+
+        let mut input = json!({ "messages": messages });
+        if let Some(sys) = system {
+            input["system"] = json!(sys);
+        }
+
+        let created = match client
+            .post(&url)
+            .header("Authorization", auth_header(&self.config.api_token))
+            .header("Content-Type", "application/json")
+            .json(&json!({ "input": input }))
+            .send()
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("prediction request failed: {}", e);
+                let _ = app.emit("cloudflare-error", json!({"message": e.to_string()}));
+                return Err(format!("Replicate request failed: {}", e));
+            }
+        };
+
+        if !created.status().is_success() {
+            let status = created.status();
+            let body = created.text().await.unwrap_or_default();
+            error!(%status, "prediction HTTP error: {}", body);
+            let _ = app.emit("cloudflare-error", json!({"message": format!("HTTP {}: {}", status, body)}));
+            return Err(format!("Replicate HTTP {}", status));
+        }
+
+        let prediction: serde_json::Value = created
+            .json()
+            .await
+            .map_err(|e| format!("JSON parse error: {}", e))?;
+        let get_url = prediction
+            .pointer("/urls/get")
+            .and_then(|u| u.as_str())
+            .ok_or_else(|| "prediction response missing urls.get".to_string())?
+            .to_string();
+
+        let mut emitted = 0usize;
+        let mut delay_ms = 500u64;
+
+        loop {
+            if cancel.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                info!("replicate stream cancelled");
+                let _ = app.emit("cloudflare-cancelled", json!({}));
+                return Ok(());
+            }
+
+            let polled: serde_json::Value = client
+                .get(&get_url)
+                .header("Authorization", auth_header(&self.config.api_token))
+                .send()
+                .await
+                .map_err(|e| format!("poll request failed: {}", e))?
+                .json()
+                .await
+                .map_err(|e| format!("poll JSON parse error: {}", e))?;
+
+            // Emit any output elements we haven't seen yet.
+            if let Some(output) = polled.get("output").and_then(|o| o.as_array()) {
+                for element in output.iter().skip(emitted) {
+                    if let Some(token) = element.as_str() {
+                        let _ = app.emit("cloudflare-token", json!({"token": token}));
+                        if let Some(label) = &eval_target {
+                            let _ = crate::eval_bridge::eval_on_main_thread(
+                                app,
+                                label,
+                                crate::eval_bridge::append_token_script(token),
+                            )
+                            .await;
+                        }
+                    }
+                }
+                emitted = output.len();
+            }
+
+            match polled.get("status").and_then(|s| s.as_str()) {
+                Some("succeeded") => {
+                    let _ = app.emit("cloudflare-done", json!({}));
+                    return Ok(());
+                }
+                Some(status @ ("failed" | "canceled")) => {
+                    let detail = polled
+                        .get("error")
+                        .and_then(|e| e.as_str())
+                        .unwrap_or(status);
+                    error!(status, "prediction ended: {}", detail);
+                    let _ = app.emit("cloudflare-error", json!({"message": detail}));
+                    return Err(format!("Replicate prediction {}: {}", status, detail));
+                }
+                _ => {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+                    delay_ms = (delay_ms * 2).min(2000);
+                }
+            }
+        }
+    }
+
+    async fn ingest_memory(
+        &self,
+        _text: String,
+        _metadata: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        Err("memory ingest is not supported by the replicate provider".to_string())
+    }
+
+    async fn search_memory(
+        &self,
+        _query: String,
+        _top_k: Option<u32>,
+    ) -> Result<Vec<MemoryMatch>, String> {
+        Err("memory search is not supported by the replicate provider".to_string())
+    }
+}
+
+/// Parse a Vectorize/`/api/memory/search` response body into `MemoryMatch`es.
+fn parse_matches(data: &serde_json::Value) -> Vec<MemoryMatch> {
+    data["matches"]
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|m| {
+                    Some(MemoryMatch {
+                        id: m["id"].as_str()?.to_string(),
+                        score: m["score"].as_f64().unwrap_or(0.0),
+                        text: m["text"].as_str().unwrap_or("").to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 // ─────────────────────────── Chat ────────────────────────────────────────────
 
 /// Stream chat tokens from the Cloudflare Worker and emit them as Tauri events.
 /// Each token is emitted as `cloudflare-token` event with a `{token: "..."}` payload.
 /// A final `cloudflare-done` event is emitted when streaming ends.
 /// On error, falls back gracefully and emits `cloudflare-error`.
+///
+/// When `eval_target` names a window, each token is additionally appended
+/// straight into that window's DOM via the main-thread eval bridge, avoiding an
+/// event round-trip for streamed output.
+///
+/// A stream stopped through `cancel_chat_cloudflare` emits `cloudflare-cancelled`
+/// instead of `cloudflare-done`.
 #[tauri::command]
+#[instrument(skip_all, fields(endpoint = %config.endpoint, messages = messages.len(), tools = tools.len()))]
 pub async fn stream_chat_cloudflare(
     app: AppHandle,
+    registry: tauri::State<'_, ToolCallRegistry>,
+    cancels: tauri::State<'_, ChatCancelRegistry>,
     config: CloudflareConfig,
     messages: Vec<ChatMessage>,
     system: Option<String>,
+    eval_target: Option<String>,
+    tools: Vec<ToolSpec>,
+    request_id: Option<String>,
+) -> Result<(), String> {
+    // Register a cancellation flag the `cancel_chat_cloudflare` command can flip.
+    let cancel = request_id.as_ref().map(|id| cancels.register(id));
+
+    let provider = provider_for(config);
+    let result = provider
+        .stream_chat(&app, &registry, messages, system, eval_target, tools, cancel)
+        .await;
+
+    // Always drop the flag from the registry once the stream finishes.
+    if let Some(id) = &request_id {
+        cancels.remove(id);
+    }
+    result
+}
+
+/// Drive the tool-calling loop for a chat stream.
+#[allow(clippy::too_many_arguments)]
+async fn run_chat_turns(
+    app: &AppHandle,
+    registry: &ToolCallRegistry,
+    config: &CloudflareConfig,
+    messages: Vec<ChatMessage>,
+    system: Option<String>,
+    eval_target: Option<String>,
+    tools: Vec<ToolSpec>,
+    cancel: Option<&Arc<AtomicBool>>,
 ) -> Result<(), String> {
     let client = build_client();
     let url = format!("{}/api/chat", config.endpoint.trim_end_matches('/'));
 
+    // Accumulated conversation that grows with assistant tool-call turns and the
+    // tool results fed back in.
+    let mut messages = messages;
+    // Session cache so an identical tool call isn't executed twice.
+    let mut results_cache: HashMap<String, String> = HashMap::new();
+
+    for step in 0..MAX_TOOL_STEPS {
+        match stream_one_turn(
+            app,
+            &client,
+            &url,
+            config,
+            &messages,
+            &system,
+            &tools,
+            eval_target.as_deref(),
+            cancel,
+        )
+        .await?
+        {
+            TurnOutcome::Done => {
+                let _ = app.emit("cloudflare-done", json!({}));
+                return Ok(());
+            }
+            TurnOutcome::Cancelled => {
+                info!("chat stream cancelled");
+                let _ = app.emit("cloudflare-cancelled", json!({}));
+                return Ok(());
+            }
+            TurnOutcome::ToolCalls(calls) => {
+                info!(step, count = calls.len(), "turn requested tool calls");
+
+                // Record the assistant turn that requested the calls.
+                messages.push(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: String::new(),
+                    tool_call_id: None,
+                    tool_calls: Some(calls.iter().map(tool_call_to_json).collect()),
+                });
+
+                let results = resolve_tool_results(app, registry, &calls, &mut results_cache).await?;
+                for result in results {
+                    messages.push(ChatMessage {
+                        role: "tool".to_string(),
+                        content: result.content,
+                        tool_call_id: Some(result.tool_call_id),
+                        tool_calls: None,
+                    });
+                }
+            }
+        }
+    }
+
+    warn!(cap = MAX_TOOL_STEPS, "tool-calling loop cap reached");
+    let _ = app.emit(
+        "cloudflare-error",
+        json!({"message": format!("tool-calling loop exceeded {} steps", MAX_TOOL_STEPS)}),
+    );
+    Err(format!("tool-calling loop exceeded {} steps", MAX_TOOL_STEPS))
+}
+
+/// Cancel an in-flight chat stream previously started with the matching
+/// `request_id`. The streaming loop observes the flag on its next iteration,
+/// drops the connection and emits `cloudflare-cancelled`.
+#[tauri::command]
+#[instrument(skip(cancels))]
+pub fn cancel_chat_cloudflare(
+    cancels: tauri::State<'_, ChatCancelRegistry>,
+    request_id: String,
+) -> Result<(), String> {
+    if cancels.cancel(&request_id) {
+        Ok(())
+    } else {
+        Err(format!("no in-flight chat stream for request `{}`", request_id))
+    }
+}
+
+/// Submit the results of executing the tool calls for `turn_id`, waking the
+/// parked `stream_chat_cloudflare` loop so it can continue the conversation.
+#[tauri::command]
+#[instrument(skip(registry, results), fields(results = results.len()))]
+pub fn submit_tool_results(
+    registry: tauri::State<'_, ToolCallRegistry>,
+    turn_id: u64,
+    results: Vec<ToolResult>,
+) -> Result<(), String> {
+    registry.resolve(turn_id, results)
+}
+
+/// Outcome of a single streamed chat turn.
+enum TurnOutcome {
+    /// The turn finished with normal text output and no tool calls.
+    Done,
+    /// The turn finished requesting tool calls.
+    ToolCalls(Vec<ToolCall>),
+    /// The turn was cancelled via its request-id flag.
+    Cancelled,
+}
+
+/// A parsed Server-Sent Event.
+#[derive(Default)]
+pub(crate) struct SseEvent {
+    /// `event:` field; `None` when absent (a plain `message` event).
+    event: Option<String>,
+    /// `id:` field.
+    id: Option<String>,
+    /// Concatenation of all `data:` lines in the event, joined with `\n`.
+    pub(crate) data: String,
+}
+
+/// Incremental SSE decoder that buffers bytes across chunk boundaries.
+///
+/// Each chunk is appended to a persistent byte buffer; only lines terminated by
+/// `\n` are consumed, so multi-byte UTF-8 sequences split across TCP chunks are
+/// never cut and `data:` events split across chunks are never dropped. Supports
+/// the full grammar used by the Worker: multi-line `data:` concatenation, blank
+/// lines as event boundaries, and `event:`/`id:` fields.
+#[derive(Default)]
+pub(crate) struct SseDecoder {
+    buf: Vec<u8>,
+    event: Option<String>,
+    id: Option<String>,
+    data_lines: Vec<String>,
+}
+
+impl SseDecoder {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a chunk and return any events it completed.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend_from_slice(bytes);
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buf.iter().position(|&b| b == b'\n') {
+            // Drain one complete line (now guaranteed to hold whole UTF-8).
+            let mut line: Vec<u8> = self.buf.drain(..=pos).collect();
+            line.pop(); // trailing \n
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            let line = String::from_utf8_lossy(&line);
+
+            if line.is_empty() {
+                if let Some(event) = self.take_event() {
+                    events.push(event);
+                }
+                continue;
+            }
+            // Comment line.
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((f, v)) => (f, v.strip_prefix(' ').unwrap_or(v)),
+                None => (line.as_ref(), ""),
+            };
+            match field {
+                "data" => self.data_lines.push(value.to_string()),
+                "event" => self.event = Some(value.to_string()),
+                "id" => self.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        events
+    }
+
+    /// Flush any event accumulated without a trailing blank line at stream end.
+    pub(crate) fn finish(&mut self) -> Option<SseEvent> {
+        self.take_event()
+    }
+
+    fn take_event(&mut self) -> Option<SseEvent> {
+        if self.data_lines.is_empty() && self.event.is_none() {
+            return None;
+        }
+        Some(SseEvent {
+            event: self.event.take(),
+            id: self.id.take(),
+            data: std::mem::take(&mut self.data_lines).join("\n"),
+        })
+    }
+}
+
+/// Issue one chat request and stream it, emitting `cloudflare-token` events for
+/// text deltas and accumulating any `tool_calls` deltas. Returns once the turn
+/// ends (via `[DONE]`, `finish_reason`, or stream close).
+#[allow(clippy::too_many_arguments)]
+async fn stream_one_turn(
+    app: &AppHandle,
+    client: &Client,
+    url: &str,
+    config: &CloudflareConfig,
+    messages: &[ChatMessage],
+    system: &Option<String>,
+    tools: &[ToolSpec],
+    eval_target: Option<&str>,
+    cancel: Option<&Arc<AtomicBool>>,
+) -> Result<TurnOutcome, String> {
     let mut payload = json!({ "messages": messages });
     if let Some(sys) = system {
         payload["system"] = json!(sys);
     }
+    if !tools.is_empty() {
+        payload["tools"] = json!(tools);
+    }
 
     let response = match client
-        .post(&url)
+        .post(url)
         .header("Authorization", auth_header(&config.api_token))
         .header("Content-Type", "application/json")
         .json(&payload)
@@ -75,7 +729,7 @@ pub async fn stream_chat_cloudflare(
     {
         Ok(r) => r,
         Err(e) => {
-            eprintln!("[Cloudflare] chat request failed: {}", e);
+            error!("chat request failed: {}", e);
             let _ = app.emit("cloudflare-error", json!({"message": e.to_string()}));
             return Err(format!("Cloudflare chat failed: {}", e));
         }
@@ -84,7 +738,7 @@ pub async fn stream_chat_cloudflare(
     if !response.status().is_success() {
         let status = response.status();
         let body = response.text().await.unwrap_or_default();
-        eprintln!("[Cloudflare] chat HTTP {}: {}", status, body);
+        error!(%status, "chat HTTP error: {}", body);
         let _ = app.emit("cloudflare-error", json!({"message": format!("HTTP {}: {}", status, body)}));
         return Err(format!("Cloudflare HTTP {}", status));
     }
@@ -92,119 +746,255 @@ pub async fn stream_chat_cloudflare(
     use futures_util::StreamExt;
     let mut stream = response.bytes_stream();
 
+    // tool call index -> partially accumulated call
+    let mut tool_acc: HashMap<u64, ToolCall> = HashMap::new();
+    let mut saw_tool_calls = false;
+    let mut decoder = SseDecoder::new();
+
     while let Some(chunk) = stream.next().await {
+        // Check for cancellation before processing each chunk; dropping the
+        // stream here closes the underlying connection.
+        if cancel.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            drop(stream);
+            return Ok(TurnOutcome::Cancelled);
+        }
         match chunk {
             Ok(bytes) => {
-                let text = String::from_utf8_lossy(&bytes);
-                for line in text.lines() {
-                    if let Some(data) = line.strip_prefix("data: ") {
-                        if data.trim() == "[DONE]" {
-                            let _ = app.emit("cloudflare-done", json!({}));
-                            return Ok(());
-                        }
-                        // Try to extract `response` field from Workers AI SSE JSON
-                        if let Ok(v) = serde_json::from_str::<serde_json::Value>(data) {
-                            if let Some(token) = v.get("response").and_then(|t| t.as_str()) {
-                                let _ = app.emit("cloudflare-token", json!({"token": token}));
-                            }
-                        }
+                for event in decoder.feed(&bytes) {
+                    if let Some(outcome) = handle_sse_event(
+                        app,
+                        &event,
+                        eval_target,
+                        &mut tool_acc,
+                        &mut saw_tool_calls,
+                    )
+                    .await
+                    {
+                        return Ok(outcome);
                     }
                 }
             }
             Err(e) => {
-                eprintln!("[Cloudflare] stream error: {}", e);
+                error!("stream error: {}", e);
                 let _ = app.emit("cloudflare-error", json!({"message": e.to_string()}));
                 return Err(format!("Stream error: {}", e));
             }
         }
     }
 
-    let _ = app.emit("cloudflare-done", json!({}));
-    Ok(())
+    // Flush any trailing event the stream closed without a blank line.
+    if let Some(event) = decoder.finish() {
+        if let Some(outcome) =
+            handle_sse_event(app, &event, eval_target, &mut tool_acc, &mut saw_tool_calls).await
+        {
+            return Ok(outcome);
+        }
+    }
+
+    Ok(finish_turn(saw_tool_calls, tool_acc))
 }
 
-// ─────────────────────────── Memory ──────────────────────────────────────────
+/// Dispatch a single decoded SSE event. Returns `Some` when the event ends the
+/// turn (via `[DONE]` or a `finish_reason`), otherwise `None` to keep streaming.
+async fn handle_sse_event(
+    app: &AppHandle,
+    event: &SseEvent,
+    eval_target: Option<&str>,
+    tool_acc: &mut HashMap<u64, ToolCall>,
+    saw_tool_calls: &mut bool,
+) -> Option<TurnOutcome> {
+    let data = event.data.trim();
+    if data.is_empty() {
+        return None;
+    }
+    if data == "[DONE]" {
+        return Some(finish_turn(*saw_tool_calls, std::mem::take(tool_acc)));
+    }
 
-/// Send text to the Worker for chunking + embedding + Vectorize upsert.
-#[tauri::command]
-pub async fn ingest_memory_cloudflare(
-    config: CloudflareConfig,
-    text: String,
-    metadata: Option<serde_json::Value>,
-) -> Result<serde_json::Value, String> {
-    let client = build_client();
-    let url = format!("{}/api/memory/ingest", config.endpoint.trim_end_matches('/'));
+    // Named events carry structured side-channel payloads; surface errors and
+    // usage stats distinctly rather than feeding them through the delta path.
+    match event.event.as_deref() {
+        Some("error") => {
+            error!("worker stream error: {}", data);
+            let _ = app.emit("cloudflare-error", json!({ "message": data }));
+            return None;
+        }
+        Some("usage") => {
+            if let Ok(v) = serde_json::from_str::<serde_json::Value>(data) {
+                let _ = app.emit("cloudflare-usage", v);
+            }
+            return None;
+        }
+        _ => {}
+    }
+
+    let v = serde_json::from_str::<serde_json::Value>(data).ok()?;
+    // Plain Workers AI text delta.
+    if let Some(token) = v.get("response").and_then(|t| t.as_str()) {
+        emit_token(app, token, eval_target).await;
+    }
+    // OpenAI-style text delta.
+    if let Some(token) = v
+        .pointer("/choices/0/delta/content")
+        .and_then(|t| t.as_str())
+    {
+        emit_token(app, token, eval_target).await;
+    }
+    // Accumulate tool_calls deltas.
+    if let Some(deltas) = v
+        .pointer("/choices/0/delta/tool_calls")
+        .and_then(|t| t.as_array())
+    {
+        *saw_tool_calls = true;
+        accumulate_tool_calls(tool_acc, deltas);
+    }
+    // Explicit finish_reason ends the turn.
+    if let Some(reason) = v
+        .pointer("/choices/0/finish_reason")
+        .and_then(|r| r.as_str())
+    {
+        if reason == "tool_calls" {
+            return Some(finish_turn(true, std::mem::take(tool_acc)));
+        }
+        return Some(finish_turn(*saw_tool_calls, std::mem::take(tool_acc)));
+    }
+    None
+}
 
-    let mut payload = json!({ "text": text });
-    if let Some(m) = metadata {
-        payload["metadata"] = m;
+/// Emit a text token to the frontend and, optionally, straight into a window's DOM.
+async fn emit_token(app: &AppHandle, token: &str, eval_target: Option<&str>) {
+    let _ = app.emit("cloudflare-token", json!({"token": token}));
+    if let Some(label) = eval_target {
+        let _ = crate::eval_bridge::eval_on_main_thread(
+            app,
+            label,
+            crate::eval_bridge::append_token_script(token),
+        )
+        .await;
     }
+}
 
-    let resp = client
-        .post(&url)
-        .header("Authorization", auth_header(&config.api_token))
-        .header("Content-Type", "application/json")
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+/// Merge a batch of streamed `tool_calls` delta entries into the accumulator,
+/// keyed by call index; argument fragments are concatenated in arrival order.
+fn accumulate_tool_calls(acc: &mut HashMap<u64, ToolCall>, deltas: &[serde_json::Value]) {
+    for delta in deltas {
+        let index = delta.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+        let entry = acc.entry(index).or_insert_with(|| ToolCall {
+            id: String::new(),
+            name: String::new(),
+            arguments: String::new(),
+            needs_confirmation: false,
+        });
+        if let Some(id) = delta.get("id").and_then(|i| i.as_str()) {
+            entry.id = id.to_string();
+        }
+        if let Some(name) = delta.pointer("/function/name").and_then(|n| n.as_str()) {
+            entry.name = name.to_string();
+            entry.needs_confirmation = name.starts_with("may_");
+        }
+        if let Some(args) = delta.pointer("/function/arguments").and_then(|a| a.as_str()) {
+            entry.arguments.push_str(args);
+        }
+    }
+}
 
-    if !resp.status().is_success() {
-        return Err(format!("HTTP {}", resp.status()));
+/// Finalize a turn: if any tool calls were accumulated, return them ordered by
+/// index; otherwise the turn is done.
+fn finish_turn(saw_tool_calls: bool, acc: HashMap<u64, ToolCall>) -> TurnOutcome {
+    if !saw_tool_calls || acc.is_empty() {
+        return TurnOutcome::Done;
     }
+    let mut calls: Vec<(u64, ToolCall)> = acc.into_iter().collect();
+    calls.sort_by_key(|(index, _)| *index);
+    TurnOutcome::ToolCalls(calls.into_iter().map(|(_, c)| c).collect())
+}
 
-    resp.json::<serde_json::Value>()
-        .await
-        .map_err(|e| format!("JSON parse error: {}", e))
+/// Serialize an accumulated tool call back into the OpenAI assistant-message shape.
+fn tool_call_to_json(call: &ToolCall) -> serde_json::Value {
+    json!({
+        "id": call.id,
+        "type": "function",
+        "function": { "name": call.name, "arguments": call.arguments },
+    })
 }
 
-/// Query Vectorize for top-K semantic matches.
-#[tauri::command]
-pub async fn search_memory_cloudflare(
-    config: CloudflareConfig,
-    query: String,
-    top_k: Option<u32>,
-) -> Result<Vec<MemoryMatch>, String> {
-    let client = build_client();
-    let url = format!("{}/api/memory/search", config.endpoint.trim_end_matches('/'));
+/// Cache key for a tool call: identical `(name, arguments)` pairs share a
+/// result, so a model that repeats the same call within a session is only
+/// executed once. The per-call `id` is deliberately excluded — it is unique to
+/// every call and would defeat deduplication.
+fn tool_cache_key(call: &ToolCall) -> String {
+    format!("{}\u{0}{}", call.name, call.arguments)
+}
 
-    let payload = json!({
-        "query": query,
-        "top_k": top_k.unwrap_or(config.rag_top_k.unwrap_or(5)),
-    });
+/// Obtain results for `calls`: reuse cached results for identical calls made
+/// earlier in the session, and ask the frontend to execute the rest via a
+/// `cloudflare-tool-call` event, awaiting `submit_tool_results`.
+async fn resolve_tool_results(
+    app: &AppHandle,
+    registry: &ToolCallRegistry,
+    calls: &[ToolCall],
+    cache: &mut HashMap<String, String>,
+) -> Result<Vec<ToolResult>, String> {
+    let mut results: Vec<ToolResult> = Vec::with_capacity(calls.len());
+    let mut to_execute: Vec<&ToolCall> = Vec::new();
 
-    let resp = client
-        .post(&url)
-        .header("Authorization", auth_header(&config.api_token))
-        .header("Content-Type", "application/json")
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    for call in calls {
+        if let Some(content) = cache.get(&tool_cache_key(call)) {
+            results.push(ToolResult {
+                tool_call_id: call.id.clone(),
+                content: content.clone(),
+            });
+        } else {
+            to_execute.push(call);
+        }
+    }
+
+    if !to_execute.is_empty() {
+        let (turn_id, rx) = registry.register();
+        let _ = app.emit(
+            "cloudflare-tool-call",
+            json!({ "turn_id": turn_id, "tool_calls": to_execute }),
+        );
 
-    if !resp.status().is_success() {
-        return Err(format!("HTTP {}", resp.status()));
+        let executed = rx
+            .await
+            .map_err(|_| "tool-call turn cancelled before results arrived".to_string())?;
+
+        // Map each returned result back to its originating call so the cache is
+        // keyed by `(name, arguments)` rather than the unique `tool_call_id`.
+        let by_id: HashMap<&str, &ToolCall> =
+            to_execute.iter().map(|c| (c.id.as_str(), *c)).collect();
+        for result in executed {
+            if let Some(call) = by_id.get(result.tool_call_id.as_str()) {
+                cache.insert(tool_cache_key(call), result.content.clone());
+            }
+            results.push(result);
+        }
     }
 
-    let data: serde_json::Value = resp
-        .json()
-        .await
-        .map_err(|e| format!("JSON parse error: {}", e))?;
+    Ok(results)
+}
 
-    let matches = data["matches"]
-        .as_array()
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|m| {
-                    Some(MemoryMatch {
-                        id: m["id"].as_str()?.to_string(),
-                        score: m["score"].as_f64().unwrap_or(0.0),
-                        text: m["text"].as_str().unwrap_or("").to_string(),
-                    })
-                })
-                .collect()
-        })
-        .unwrap_or_default();
+// ─────────────────────────── Memory ──────────────────────────────────────────
+
+/// Send text to the configured provider for chunking + embedding + upsert.
+#[tauri::command]
+#[instrument(skip_all, fields(endpoint = %config.endpoint, bytes = text.len()))]
+pub async fn ingest_memory_cloudflare(
+    config: CloudflareConfig,
+    text: String,
+    metadata: Option<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    provider_for(config).ingest_memory(text, metadata).await
+}
 
-    Ok(matches)
+/// Query the configured provider for top-K semantic matches.
+#[tauri::command]
+#[instrument(skip_all, fields(endpoint = %config.endpoint))]
+pub async fn search_memory_cloudflare(
+    config: CloudflareConfig,
+    query: String,
+    top_k: Option<u32>,
+) -> Result<Vec<MemoryMatch>, String> {
+    provider_for(config).search_memory(query, top_k).await
 }
@@ -0,0 +1,59 @@
+// eval_bridge.rs — main-thread JavaScript eval bridge
+//
+// `app.emit` broadcasts over the event bus, forcing every window to subscribe
+// and re-render. For streamed chat output we want a targeted, low-latency path
+// that writes partial tokens straight into the response window's DOM. This
+// module runs JavaScript directly in a named webview *on the main thread* (the
+// only safe thread for `WebviewWindow::eval` on some platforms) and resolves
+// the returned future once the eval has actually executed, via a one-shot
+// channel, so callers can await completion/errors instead of fire-and-forget.
+
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+
+/// Evaluate `script` in the webview labelled `label`, on the main thread, and
+/// await its completion.
+#[tauri::command]
+pub async fn eval_in_window(app: AppHandle, label: String, script: String) -> Result<(), String> {
+    eval_on_main_thread(&app, &label, script).await
+}
+
+/// Internal helper: run `script` in `label`'s webview on the main thread and
+/// resolve once it has executed. Shared by the `eval_in_window` command and the
+/// chat streaming appender path.
+pub async fn eval_on_main_thread(
+    app: &AppHandle,
+    label: &str,
+    script: String,
+) -> Result<(), String> {
+    let window = app
+        .get_webview_window(label)
+        .ok_or_else(|| format!("window `{}` not found", label))?;
+
+    let (tx, rx) = oneshot::channel();
+    let eval_window = window.clone();
+    window
+        .run_on_main_thread(move || {
+            let result = eval_window.eval(&script).map_err(|e| e.to_string());
+            // Acknowledge once the eval has run; ignore send errors if the
+            // caller has already gone away.
+            let _ = tx.send(result);
+        })
+        .map_err(|e| format!("could not schedule eval on main thread: {}", e))?;
+
+    rx.await
+        .map_err(|_| "eval acknowledgement channel dropped".to_string())?
+}
+
+/// Build a small appender snippet that writes `token` to the end of the response
+/// stream container (falling back to `<body>`), so streamed tokens land with
+/// minimal latency and no event round-trip.
+pub fn append_token_script(token: &str) -> String {
+    // Serialize through serde so arbitrary token text is safely escaped.
+    let literal = serde_json::to_string(token).unwrap_or_else(|_| "\"\"".to_string());
+    format!(
+        "(function(t){{var el=document.getElementById('pointer-stream')||document.body;\
+if(el){{el.insertAdjacentText('beforeend',t);}}}})({});",
+        literal
+    )
+}
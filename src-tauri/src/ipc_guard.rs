@@ -0,0 +1,113 @@
+// ipc_guard.rs — origin guard for the Tauri IPC bridge
+//
+// Every `#[tauri::command]` is reachable from any document loaded in a webview.
+// The overlay and response windows render model output and other semi-trusted
+// content, so injected remote script could otherwise drive privileged commands
+// (`start_backend`, `stream_chat_cloudflare`, the memory commands, …) to
+// exfiltrate data or spawn the sidecar.
+//
+// `check_invoke` runs as a `tauri::Builder` invoke interceptor: it resolves the
+// calling webview's current URL and refuses the call unless the scheme is one
+// of the app's local asset protocols (`tauri://`/`asset://`), the host is the
+// bundled asset host (`tauri.localhost`, used by Windows/Linux release builds
+// over `http(s)://`), or — in debug builds — the loopback dev server. An
+// `IpcAllowlist` managed state lets specific
+// windows opt individual commands back in for remote origins.
+
+use std::collections::{HashMap, HashSet};
+
+use tauri::ipc::InvokeMessage;
+use tauri::{Manager, Runtime};
+
+/// Schemes served by Tauri's own asset pipeline — always trusted.
+///
+/// Deliberately excludes `data:` (fully attacker-controllable document content)
+/// and `ipc:`, so a window navigated or redirected to such a document cannot
+/// drive privileged commands.
+const TRUSTED_SCHEMES: &[&str] = &["tauri", "asset"];
+
+/// Hosts served by Tauri's own asset pipeline, regardless of scheme. On Windows
+/// and Linux the production webview loads the bundled app from
+/// `http(s)://tauri.localhost`, so the origin is trusted by host rather than by
+/// scheme (which is the non-local `http`/`https`).
+const TRUSTED_HOSTS: &[&str] = &["tauri.localhost"];
+
+/// Per-window opt-in allowlist for commands that may run from a remote origin.
+///
+/// Keyed by window label; the value is the set of command names that window is
+/// permitted to invoke even when its document was loaded over `http(s)://`.
+#[derive(Debug, Default)]
+pub struct IpcAllowlist {
+    windows: HashMap<String, HashSet<String>>,
+}
+
+impl IpcAllowlist {
+    /// Build an allowlist from `(window_label, command_name)` pairs.
+    pub fn new<I, L, C>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (L, C)>,
+        L: Into<String>,
+        C: Into<String>,
+    {
+        let mut windows: HashMap<String, HashSet<String>> = HashMap::new();
+        for (label, command) in entries {
+            windows.entry(label.into()).or_default().insert(command.into());
+        }
+        Self { windows }
+    }
+
+    /// Whether `label` is allowed to invoke `command` from a remote origin.
+    fn allows(&self, label: &str, command: &str) -> bool {
+        self.windows
+            .get(label)
+            .is_some_and(|cmds| cmds.contains(command))
+    }
+}
+
+/// Validate the origin of an incoming IPC call.
+///
+/// Returns `Ok(())` when the call may proceed and `Err(reason)` when it must be
+/// rejected before the command body runs.
+pub fn check_invoke<R: Runtime>(message: &InvokeMessage<R>) -> Result<(), String> {
+    let webview = message.webview();
+    let command = message.command();
+    let label = webview.label().to_string();
+
+    let url = webview
+        .url()
+        .map_err(|e| format!("could not resolve calling window URL: {}", e))?;
+    let scheme = url.scheme();
+
+    if TRUSTED_SCHEMES.contains(&scheme) {
+        return Ok(());
+    }
+
+    // The bundled asset host is trusted regardless of scheme (it is served over
+    // `http(s)://tauri.localhost` on Windows/Linux release builds).
+    if url.host_str().is_some_and(|h| TRUSTED_HOSTS.contains(&h)) {
+        return Ok(());
+    }
+
+    // In debug builds the frontend is served from the dev server (http://localhost).
+    #[cfg(debug_assertions)]
+    if matches!(scheme, "http" | "https") && is_loopback_host(url.host_str()) {
+        return Ok(());
+    }
+
+    // Remote origin: only allowed if this window opted the command back in.
+    if let Some(allowlist) = webview.app_handle().try_state::<IpcAllowlist>() {
+        if allowlist.allows(&label, command) {
+            return Ok(());
+        }
+    }
+
+    Err(format!(
+        "command `{}` refused: untrusted origin `{}` in window `{}`",
+        command, url, label
+    ))
+}
+
+#[cfg(debug_assertions)]
+fn is_loopback_host(host: Option<&str>) -> bool {
+    matches!(host, Some("localhost") | Some("127.0.0.1") | Some("[::1]"))
+}
@@ -0,0 +1,186 @@
+// kv.rs — Cloudflare Workers KV REST client for Pointer AI
+//
+// Exposes KV as a small object store over the Cloudflare API's
+// `/accounts/{acct}/storage/kv/namespaces/{ns}` endpoints: `kv_get`/`kv_put`/
+// `kv_delete` read, write, and remove a single value, and `kv_list` walks the
+// `keys?cursor=` pagination (following `result_info.cursor` until it comes back
+// empty) to enumerate every key under a prefix.
+//
+// Pointer AI uses this to cache the `/api/settings` config, to keep per-memory
+// metadata/titles next to the Vectorize ids returned by
+// `ingest_memory_cloudflare`, and to persist chat session history without a
+// separate database. The account id and namespace id come in as arguments from
+// the frontend; the bearer token reuses `cloudflare::auth_header`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, instrument};
+
+use crate::cloudflare::{auth_header, build_client};
+
+/// Identifies a KV namespace and the credentials to reach it. Passed by the
+/// frontend like `CloudflareConfig`, with which it shares only `api_token`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KvConfig {
+    pub account_id: String,
+    pub namespace_id: String,
+    pub api_token: String,
+}
+
+impl KvConfig {
+    /// Base URL of this namespace, e.g.
+    /// `.../accounts/{acct}/storage/kv/namespaces/{ns}`.
+    fn base(&self) -> String {
+        format!(
+            "https://api.cloudflare.com/client/v4/accounts/{}/storage/kv/namespaces/{}",
+            self.account_id, self.namespace_id
+        )
+    }
+
+    /// URL of a single value, with `key` percent-encoded as one path segment.
+    fn value_url(&self, key: &str) -> Result<reqwest::Url, String> {
+        let mut url = reqwest::Url::parse(&self.base())
+            .map_err(|e| format!("Invalid KV endpoint: {}", e))?;
+        url.path_segments_mut()
+            .map_err(|_| "Invalid KV endpoint".to_string())?
+            .push("values")
+            .push(key);
+        Ok(url)
+    }
+}
+
+/// Fetch a single value by key. Returns `None` when the key is absent (404).
+#[tauri::command]
+#[instrument(skip_all, fields(namespace = %config.namespace_id, key = %key))]
+pub async fn kv_get(config: KvConfig, key: String) -> Result<Option<String>, String> {
+    let client = build_client();
+    let url = config.value_url(&key)?;
+
+    let resp = client
+        .get(url)
+        .header("Authorization", auth_header(&config.api_token))
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        let status = resp.status();
+        error!(%status, "kv get failed");
+        return Err(format!("HTTP {}", status));
+    }
+
+    resp.text()
+        .await
+        .map(Some)
+        .map_err(|e| format!("Read error: {}", e))
+}
+
+/// Store `value` under `key`, overwriting any existing value.
+#[tauri::command]
+#[instrument(skip_all, fields(namespace = %config.namespace_id, key = %key, bytes = value.len()))]
+pub async fn kv_put(config: KvConfig, key: String, value: String) -> Result<(), String> {
+    let client = build_client();
+    let url = config.value_url(&key)?;
+
+    let resp = client
+        .put(url)
+        .header("Authorization", auth_header(&config.api_token))
+        .body(value)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        error!(%status, "kv put failed");
+        return Err(format!("HTTP {}", status));
+    }
+    Ok(())
+}
+
+/// Delete the value stored under `key`. Deleting a missing key is a no-op.
+#[tauri::command]
+#[instrument(skip_all, fields(namespace = %config.namespace_id, key = %key))]
+pub async fn kv_delete(config: KvConfig, key: String) -> Result<(), String> {
+    let client = build_client();
+    let url = config.value_url(&key)?;
+
+    let resp = client
+        .delete(url)
+        .header("Authorization", auth_header(&config.api_token))
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        error!(%status, "kv delete failed");
+        return Err(format!("HTTP {}", status));
+    }
+    Ok(())
+}
+
+/// List every key name in the namespace, optionally restricted to `prefix`.
+///
+/// KV returns at most 1000 keys per page; this follows `result_info.cursor`
+/// until it comes back empty, concatenating the `name` of each listed key.
+#[tauri::command]
+#[instrument(skip_all, fields(namespace = %config.namespace_id))]
+pub async fn kv_list(config: KvConfig, prefix: Option<String>) -> Result<Vec<String>, String> {
+    let client = build_client();
+    let mut keys = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let mut req = client
+            .get(format!("{}/keys", config.base()))
+            .header("Authorization", auth_header(&config.api_token))
+            .query(&[("limit", "1000")]);
+        if let Some(p) = &prefix {
+            req = req.query(&[("prefix", p)]);
+        }
+        if let Some(c) = &cursor {
+            req = req.query(&[("cursor", c)]);
+        }
+
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            error!(%status, "kv list failed");
+            return Err(format!("HTTP {}", status));
+        }
+
+        let body: Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("JSON parse error: {}", e))?;
+
+        if let Some(result) = body.get("result").and_then(|r| r.as_array()) {
+            for entry in result {
+                if let Some(name) = entry.get("name").and_then(|n| n.as_str()) {
+                    keys.push(name.to_string());
+                }
+            }
+        }
+
+        // The list endpoint signals continuation solely through a non-empty
+        // `result_info.cursor`; an absent or empty cursor means the last page.
+        match body
+            .pointer("/result_info/cursor")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+        {
+            Some(c) => cursor = Some(c.to_string()),
+            None => break,
+        }
+    }
+
+    Ok(keys)
+}
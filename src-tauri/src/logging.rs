@@ -0,0 +1,85 @@
+// logging.rs — structured tracing/observability for Pointer AI
+//
+// Replaces the scattered `println!`/`eprintln!` debug prints with a
+// `tracing`-based subsystem. `init()` installs a `tracing_subscriber` that
+// fans out to stderr (for `cargo run`) and a daily-rolling file appender under
+// the app data dir, so release builds keep actionable diagnostics for backend
+// spawning, WebSocket reconnects and overlay lifecycle. The returned
+// `WorkerGuard` must be held for the lifetime of the process or the non-blocking
+// writer drops buffered lines on exit.
+
+use std::path::PathBuf;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+/// Application identifier used to namespace the log directory under the OS data dir.
+const APP_DIR: &str = "com.pointer.ai";
+const LOG_FILE_PREFIX: &str = "pointer.log";
+
+/// Directory the rolling log files are written to: `<data_dir>/com.pointer.ai/logs`.
+///
+/// Falls back to the system temp dir when no data dir is resolvable so logging
+/// never fails to initialize.
+pub fn log_dir() -> PathBuf {
+    let base = dirs::data_dir().unwrap_or_else(std::env::temp_dir);
+    base.join(APP_DIR).join("logs")
+}
+
+/// Absolute path to the current day's log file, for the settings UI to surface.
+///
+/// The daily appender writes `pointer.log.<YYYY-MM-DD>`, not a bare
+/// `pointer.log`, so this returns the newest existing dated file (the ISO date
+/// suffix sorts lexicographically). Falls back to the log directory when none
+/// has been written yet.
+pub fn log_path() -> PathBuf {
+    let dir = log_dir();
+    let latest = std::fs::read_dir(&dir).ok().and_then(|entries| {
+        entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(LOG_FILE_PREFIX))
+            })
+            .max()
+    });
+    latest.unwrap_or(dir)
+}
+
+/// Initialize the global tracing subscriber. Call once, as early as possible in
+/// `main()`. Returns the appender guard — keep it alive for the whole program.
+pub fn init() -> Option<WorkerGuard> {
+    let dir = log_dir();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        eprintln!("⚠️  Could not create log dir {}: {}", dir.display(), e);
+    }
+
+    // `POINTER_LOG`/`RUST_LOG` override the default; default to debug for our own
+    // `pointer` targets and info for everything else.
+    let filter = EnvFilter::try_from_env("POINTER_LOG")
+        .or_else(|_| EnvFilter::try_from_default_env())
+        .unwrap_or_else(|_| EnvFilter::new("info,pointer=debug"));
+
+    let file_appender = tracing_appender::rolling::daily(&dir, LOG_FILE_PREFIX);
+    let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
+
+    let registry = tracing_subscriber::registry().with(filter).with(
+        fmt::layer()
+            .with_writer(file_writer)
+            .with_ansi(false)
+            .with_target(true),
+    );
+
+    // Mirror to stderr in debug builds so `cargo run` output stays familiar.
+    #[cfg(debug_assertions)]
+    let registry = registry.with(fmt::layer().with_writer(std::io::stderr));
+
+    if registry.try_init().is_err() {
+        // Already initialized (e.g. in tests) — not fatal.
+        return None;
+    }
+
+    Some(guard)
+}
@@ -12,16 +12,27 @@ use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 use futures_util::{StreamExt, SinkExt};
+use tracing::{debug, error, info, warn, instrument, Instrument};
 
 mod cloudflare;
-use cloudflare::{stream_chat_cloudflare, ingest_memory_cloudflare, search_memory_cloudflare};
+use cloudflare::{stream_chat_cloudflare, submit_tool_results, cancel_chat_cloudflare, ingest_memory_cloudflare, search_memory_cloudflare, ToolCallRegistry, ChatCancelRegistry};
 
-#[cfg(target_os = "macos")]
-use cocoa::appkit::{NSWindow, NSWindowStyleMask};
-#[cfg(target_os = "macos")]
-use cocoa::base::id;
-#[cfg(target_os = "macos")]
-use objc::runtime::YES;
+mod window_chrome;
+use window_chrome::apply_window_chrome;
+
+mod logging;
+
+mod ipc_guard;
+use ipc_guard::IpcAllowlist;
+
+mod eval_bridge;
+use eval_bridge::eval_in_window;
+
+mod server;
+use server::{start_proxy_server, stop_proxy_server, ProxyServerState};
+
+mod kv;
+use kv::{kv_get, kv_put, kv_delete, kv_list};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct OverlayContextData {
@@ -39,6 +50,75 @@ struct OverlayContextData {
 struct AppState {
     overlay_context: Mutex<Option<serde_json::Value>>,
     response_data: Mutex<Option<ResponseData>>,
+    // Name of the monitor the overlay was last placed on, so we can detect and
+    // log when the cursor moves to a different screen between invocations.
+    last_overlay_monitor: Mutex<Option<String>>,
+}
+
+/// Pick the monitor whose logical bounds contain the logical point `(x, y)`,
+/// falling back to the primary monitor. The cursor coordinates arriving from the
+/// Python backend are logical, while `Monitor::position`/`size` are physical, so
+/// each monitor's rect is divided by its own `scale_factor` before the test —
+/// keeping the containment check in one consistent (logical) coordinate space.
+fn monitor_at(app: &tauri::AppHandle, x: f64, y: f64) -> Option<tauri::Monitor> {
+    if let Ok(monitors) = app.available_monitors() {
+        for m in monitors {
+            let scale = m.scale_factor();
+            let pos = m.position();
+            let size = m.size();
+            let left = pos.x as f64 / scale;
+            let top = pos.y as f64 / scale;
+            let right = left + size.width as f64 / scale;
+            let bottom = top + size.height as f64 / scale;
+            if x >= left && x < right && y >= top && y < bottom {
+                return Some(m);
+            }
+        }
+    }
+    app.primary_monitor().ok().flatten()
+}
+
+/// Anchor a `w_logical`×`h_logical` window near the logical cursor point,
+/// clamped within the monitor's usable physical rect. All buffer/clamp math is
+/// done in physical pixels (converted via the monitor's `scale_factor`) so the
+/// result is correct on HiDPI and fractional-scaling displays.
+fn clamp_to_monitor(
+    monitor: &tauri::Monitor,
+    cursor_x: f64,
+    cursor_y: f64,
+    w_logical: f64,
+    h_logical: f64,
+) -> tauri::PhysicalPosition<i32> {
+    let scale = monitor.scale_factor();
+    let mpos = monitor.position();
+    let msize = monitor.size();
+
+    // Convert the incoming point and the overlay size into this monitor's
+    // physical coordinate space.
+    let cx = cursor_x * scale;
+    let cy = cursor_y * scale;
+    let w = w_logical * scale;
+    let h = h_logical * scale;
+
+    // Usable-area buffers (logical values promoted to physical).
+    let left_buffer = 20.0 * scale;
+    let right_buffer = 20.0 * scale;
+    let top_buffer = 40.0 * scale; // For menu bar
+    let bottom_buffer = 100.0 * scale; // For dock bar
+
+    let min_x = mpos.x as f64 + left_buffer;
+    let max_x = mpos.x as f64 + msize.width as f64 - w - right_buffer;
+    let min_y = mpos.y as f64 + top_buffer;
+    let max_y = mpos.y as f64 + msize.height as f64 - h - bottom_buffer;
+
+    // Center horizontally on the cursor, anchor top at the cursor, then clamp.
+    let px = (cx - w / 2.0).clamp(min_x, max_x.max(min_x));
+    let py = cy.clamp(min_y, max_y.max(min_y));
+
+    tauri::PhysicalPosition {
+        x: px as i32,
+        y: py as i32,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,58 +130,15 @@ struct ResponseData {
     metadata: Option<serde_json::Value>,
 }
 
-#[cfg(target_os = "macos")]
-fn apply_macos_window_effects(window: &tauri::WebviewWindow) {
-    use cocoa::appkit::NSWindowTitleVisibility;
-    use cocoa::base::nil;
-    
-    let window_label = window.label().to_string();
-    let app_handle = window.app_handle().clone();
-    
-    window.run_on_main_thread(move || {
-        if let Some(window) = app_handle.get_webview_window(&window_label) {
-            unsafe {
-                let ns_window_ptr = match window.ns_window() {
-                    Ok(ptr) => ptr,
-                    Err(e) => {
-                        eprintln!("⚠️  Could not get NSWindow for macOS effects: {}", e);
-                        return;
-                    }
-                };
-                let ns_window = ns_window_ptr as id;
-                
-                // Enable rounded corners
-                ns_window.setTitlebarAppearsTransparent_(YES);
-                ns_window.setTitleVisibility_(NSWindowTitleVisibility::NSWindowTitleHidden);
-                
-                let mut style_mask = ns_window.styleMask();
-                style_mask.insert(NSWindowStyleMask::NSFullSizeContentViewWindowMask);
-                ns_window.setStyleMask_(style_mask);
-                
-                // CRITICAL: Make window background transparent to avoid black corners
-                let _: () = msg_send![ns_window, setOpaque: 0];
-                let clear_color: id = msg_send![class!(NSColor), clearColor];
-                let _: () = msg_send![ns_window, setBackgroundColor: clear_color];
-                
-                // Also make sure the content view background is transparent
-                let content_view: id = ns_window.contentView();
-                let _: () = msg_send![content_view, setWantsLayer: 1];
-                let layer: id = msg_send![content_view, layer];
-                if !layer.is_null() {
-                    let _: () = msg_send![layer, setBackgroundColor: nil];
-                }
-            }
-        }
-    }).ok();
-}
-
 #[tauri::command]
+#[instrument(skip_all, fields(window = "overlay"))]
 async fn get_overlay_context(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
     let context = state.overlay_context.lock().unwrap();
     context.clone().ok_or_else(|| "No context available".to_string())
 }
 
 #[tauri::command]
+#[instrument(skip_all, fields(window = "main"))]
 fn show_settings(app: tauri::AppHandle) -> Result<String, String> {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.show();
@@ -113,21 +150,20 @@ fn show_settings(app: tauri::AppHandle) -> Result<String, String> {
 }
 
 #[tauri::command]
+#[instrument(skip(app, context), fields(window = "overlay"))]
 async fn show_overlay(
     app: tauri::AppHandle,
     x: f64,
     y: f64,
     context: serde_json::Value,
 ) -> Result<String, String> {
-    #[cfg(debug_assertions)]
-    println!("📍 Showing overlay at: ({}, {})", x, y);
-    
+    info!(cursor.x = x, cursor.y = y, "showing overlay");
+
     // Store context in state for overlay to fetch
     if let Some(state) = app.try_state::<AppState>() {
         let mut stored_context = state.overlay_context.lock().unwrap();
         *stored_context = Some(context.clone());
-        #[cfg(debug_assertions)]
-        println!("✅ Stored overlay context in state");
+        debug!("stored overlay context in state");
     } else {
         return Err("AppState not available".to_string());
     }
@@ -135,26 +171,26 @@ async fn show_overlay(
     if let Some(window) = app.get_webview_window("overlay") {
         let _ = window.destroy();
     }
-    
+
     use tauri::webview::WebviewWindowBuilder;
-    
-    // Overlay dimensions
+
+    // Overlay dimensions (logical)
     let overlay_width = 600.0;
     let overlay_height = 80.0;
-    
-    // Get screen size
-    let monitor = match app.primary_monitor() {
-        Ok(Some(m)) => m,
-        _ => {
-            // Fallback: use default position without bounds checking
-            #[cfg(debug_assertions)]
-            println!("⚠️ Could not get monitor info, using unbounded position");
+
+    // Pick the monitor the cursor is actually on, not just the primary one.
+    let monitor = match monitor_at(&app, x, y) {
+        Some(m) => m,
+        None => {
+            // Fallback: use default position without bounds checking.
+            warn!("could not get monitor info, using unbounded position");
             let overlay = WebviewWindowBuilder::new(
                 &app,
                 "overlay",
                 tauri::WebviewUrl::App("index.html#overlay".into())
             )
             .title("Pointer Overlay")
+            .initialization_script(window_chrome::drag_region_init_script())
             .inner_size(overlay_width, overlay_height)
             .position(x - (overlay_width / 2.0), y)
             .decorations(false)
@@ -165,13 +201,10 @@ async fn show_overlay(
             .visible(false)
             .content_protected(false)
             .build();
-            
+
             match overlay {
                 Ok(window) => {
-                    #[cfg(target_os = "macos")]
-                    apply_macos_window_effects(&window);
-                    
-                    // Show immediately - context is fetched from state
+                    apply_window_chrome(&window);
                     let _ = window.show();
                     let _ = window.set_focus();
                     return Ok("Overlay shown".to_string());
@@ -180,46 +213,29 @@ async fn show_overlay(
             }
         }
     };
-    
-    let screen_size = monitor.size();
-    let screen_width = screen_size.width as f64;
-    let screen_height = screen_size.height as f64;
-    
-    // Buffer zones
-    let left_buffer = 20.0;
-    let right_buffer = 20.0;
-    let top_buffer = 40.0;  // For menu bar
-    let bottom_buffer = 100.0;  // For dock bar
-    
-    // Calculate centered position around cursor
-    let mut overlay_x = x - (overlay_width / 2.0);
-    let mut overlay_y = y;
-    
-    // Constrain X position within screen bounds
-    if overlay_x < left_buffer {
-        overlay_x = left_buffer;
-    } else if overlay_x + overlay_width > screen_width - right_buffer {
-        overlay_x = screen_width - overlay_width - right_buffer;
-    }
-    
-    // Constrain Y position within screen bounds
-    if overlay_y < top_buffer {
-        overlay_y = top_buffer;
-    } else if overlay_y + overlay_height > screen_height - bottom_buffer {
-        overlay_y = screen_height - overlay_height - bottom_buffer;
+
+    // Note (and log) when the cursor has crossed to a different monitor since
+    // the last overlay, so positioning is re-run against the new screen.
+    let monitor_name = monitor.name().cloned().unwrap_or_default();
+    if let Some(state) = app.try_state::<AppState>() {
+        let mut last = state.last_overlay_monitor.lock().unwrap();
+        if last.as_deref() != Some(monitor_name.as_str()) {
+            debug!(monitor = %monitor_name, "cursor moved to a different monitor");
+            *last = Some(monitor_name.clone());
+        }
     }
-    
-    #[cfg(debug_assertions)]
-    println!("📍 Adjusted overlay position to: ({}, {})", overlay_x, overlay_y);
-    
+
+    let position = clamp_to_monitor(&monitor, x, y, overlay_width, overlay_height);
+    debug!(x = position.x, y = position.y, "adjusted overlay position (physical)");
+
     let overlay = WebviewWindowBuilder::new(
         &app,
         "overlay",
         tauri::WebviewUrl::App("index.html#overlay".into())
     )
     .title("Pointer Overlay")
+    .initialization_script(window_chrome::drag_region_init_script())
     .inner_size(overlay_width, overlay_height)
-    .position(overlay_x, overlay_y)
     .decorations(false)
     .transparent(true)
     .always_on_top(true)
@@ -228,12 +244,14 @@ async fn show_overlay(
     .visible(false)
     .content_protected(false)
     .build();
-    
+
     match overlay {
         Ok(window) => {
-            #[cfg(target_os = "macos")]
-            apply_macos_window_effects(&window);
-            
+            apply_window_chrome(&window);
+
+            // Position in physical pixels after creation for reliable multi-monitor placement.
+            let _ = window.set_position(tauri::Position::Physical(position));
+
             // Show immediately - context is fetched from state
             let _ = window.show();
             let _ = window.set_focus();
@@ -244,10 +262,10 @@ async fn show_overlay(
 }
 
 #[tauri::command]
+#[instrument(skip(app), fields(window = "overlay"))]
 async fn hide_overlay(app: tauri::AppHandle) -> Result<String, String> {
-    #[cfg(debug_assertions)]
-    println!("Hiding overlay...");
-    
+    debug!("hiding overlay");
+
     if let Some(window) = app.get_webview_window("overlay") {
         let _ = window.close();
     }
@@ -261,6 +279,7 @@ async fn hide_overlay(app: tauri::AppHandle) -> Result<String, String> {
 }
 
 #[tauri::command]
+#[instrument(skip_all, fields(window = "response"))]
 async fn show_response_window(
     app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
@@ -270,9 +289,8 @@ async fn show_response_window(
     _x: Option<f64>,
     _y: Option<f64>,
 ) -> Result<String, String> {
-    #[cfg(debug_assertions)]
-    println!("📋 Showing response window");
-    
+    info!(window = "response", "showing response window");
+
     // Store response data in state
     let response_data = ResponseData {
         response,
@@ -282,8 +300,7 @@ async fn show_response_window(
     
     let mut stored_data = state.response_data.lock().unwrap();
     *stored_data = Some(response_data.clone());
-    #[cfg(debug_assertions)]
-    println!("✅ Stored response data in state");
+    debug!("stored response data in state");
     
     // Close existing response window if any
     if let Some(window) = app.get_webview_window("response") {
@@ -292,35 +309,40 @@ async fn show_response_window(
     
     use tauri::webview::WebviewWindowBuilder;
     
-    // Response window dimensions - compact and clean
+    // Response window dimensions - compact and clean (logical)
     let window_width = 600.0;
     let window_height = 400.0;
-    
-    // Center on screen with slight offset for better visual balance
-    let monitor = match app.primary_monitor() {
-        Ok(Some(m)) => m,
-        _ => {
-            return Err("Could not get monitor info".to_string());
-        }
+
+    // Center on the active monitor (the one under the cursor when known),
+    // falling back to the primary monitor.
+    let monitor = match _x.zip(_y).and_then(|(x, y)| monitor_at(&app, x, y)) {
+        Some(m) => m,
+        None => match app.primary_monitor() {
+            Ok(Some(m)) => m,
+            _ => return Err("Could not get monitor info".to_string()),
+        },
     };
-    
-    let screen_size = monitor.size();
-    let screen_width = screen_size.width as f64;
-    let screen_height = screen_size.height as f64;
-    
-    // Offset slightly left and up for better visual centering
-    let window_x = (screen_width - window_width) / 2.0 - 50.0;
-    let window_y = (screen_height - window_height) / 2.0 - 100.0;
-    
-    #[cfg(debug_assertions)]
-    println!("📍 Centering response window at: ({}, {})", window_x, window_y);
-    
+
+    let scale = monitor.scale_factor();
+    let mpos = monitor.position();
+    let msize = monitor.size();
+
+    // Center within the monitor's physical rect, offset slightly left and up for
+    // better visual balance (offsets promoted from logical to physical).
+    let w = window_width * scale;
+    let h = window_height * scale;
+    let window_x = (mpos.x as f64 + (msize.width as f64 - w) / 2.0 - 50.0 * scale) as i32;
+    let window_y = (mpos.y as f64 + (msize.height as f64 - h) / 2.0 - 100.0 * scale) as i32;
+
+    debug!(window_x, window_y, "centering response window (physical)");
+
     let response_window = WebviewWindowBuilder::new(
         &app,
         "response",
         tauri::WebviewUrl::App("index.html#response".into())
     )
     .title("Response")
+    .initialization_script(window_chrome::drag_region_init_script())
     .inner_size(window_width, window_height)
     .decorations(false)
     .transparent(true)
@@ -332,17 +354,15 @@ async fn show_response_window(
     
     match response_window {
         Ok(window) => {
-            #[cfg(target_os = "macos")]
-            apply_macos_window_effects(&window);
+            apply_window_chrome(&window);
             
             // Set position AFTER window creation (more reliable on macOS)
             use tauri::Position;
             let _ = window.set_position(Position::Physical(tauri::PhysicalPosition {
-                x: window_x as i32,
-                y: window_y as i32,
+                x: window_x,
+                y: window_y,
             }));
-            #[cfg(debug_assertions)]
-            println!("🎯 Set window position to: ({}, {})", window_x, window_y);
+            debug!(window_x, window_y, "set window position");
             
             std::thread::sleep(std::time::Duration::from_millis(100));
             let _ = window.show();
@@ -353,13 +373,21 @@ async fn show_response_window(
     }
 }
 
+/// Return the path of the current rolling log file so the settings UI can show it.
+#[tauri::command]
+fn get_log_path() -> Result<String, String> {
+    Ok(logging::log_path().to_string_lossy().into_owned())
+}
+
 #[tauri::command]
+#[instrument(skip_all)]
 async fn get_response_data(state: tauri::State<'_, AppState>) -> Result<ResponseData, String> {
     let data = state.response_data.lock().unwrap();
     data.clone().ok_or_else(|| "No response data available".to_string())
 }
 
 #[tauri::command]
+#[instrument(skip_all, fields(window = "response"))]
 async fn close_response_window(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<String, String> {
     // Clear the data when closing
     let mut data = state.response_data.lock().unwrap();
@@ -372,70 +400,70 @@ async fn close_response_window(app: tauri::AppHandle, state: tauri::State<'_, Ap
 }
 
 #[tauri::command]
+#[instrument(skip(app))]
 async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
-    #[cfg(debug_assertions)]
-    println!("🚀 Starting Python backend...");
-    
+    info!("starting Python backend");
+
     match app.shell().sidecar("pointer-backend") {
         Ok(sidecar_command) => {
             match sidecar_command.spawn() {
                 Ok((mut rx, _child)) => {
-                    #[cfg(debug_assertions)]
-                    println!("✅ Backend process spawned successfully");
-                    
-                    // Spawn a task to read and print backend output
-                    tauri::async_runtime::spawn(async move {
-                        use tauri_plugin_shell::process::CommandEvent;
-                        while let Some(event) = rx.recv().await {
-                            match event {
-                                CommandEvent::Stdout(line) => {
-                                    print!("[Backend] {}", String::from_utf8_lossy(&line));
-                                }
-                                CommandEvent::Stderr(line) => {
-                                    eprint!("[Backend Error] {}", String::from_utf8_lossy(&line));
-                                }
-                                CommandEvent::Error(err) => {
-                                    eprintln!("[Backend Process Error] {}", err);
-                                }
-                                CommandEvent::Terminated(payload) => {
-                                    println!("[Backend] Process terminated with code: {:?}", payload.code);
-                                    break;
+                    info!("backend process spawned successfully");
+
+                    // Spawn a task to read and forward backend output into the log.
+                    tauri::async_runtime::spawn(
+                        async move {
+                            use tauri_plugin_shell::process::CommandEvent;
+                            while let Some(event) = rx.recv().await {
+                                match event {
+                                    CommandEvent::Stdout(line) => {
+                                        info!(target: "pointer::backend", "{}", String::from_utf8_lossy(&line).trim_end());
+                                    }
+                                    CommandEvent::Stderr(line) => {
+                                        warn!(target: "pointer::backend", "{}", String::from_utf8_lossy(&line).trim_end());
+                                    }
+                                    CommandEvent::Error(err) => {
+                                        error!(target: "pointer::backend", "{}", err);
+                                    }
+                                    CommandEvent::Terminated(payload) => {
+                                        info!(target: "pointer::backend", code = ?payload.code, "process terminated");
+                                        break;
+                                    }
+                                    _ => {}
                                 }
-                                _ => {}
                             }
                         }
-                    });
-                    
+                        .instrument(tracing::info_span!("backend_reader")),
+                    );
+
                     Ok("Backend started".to_string())
                 }
                 Err(e) => {
                     let err_msg = format!("Failed to spawn backend: {}", e);
-                    eprintln!("❌ {}", err_msg);
+                    error!("{}", err_msg);
                     Err(err_msg)
                 }
             }
         }
         Err(e) => {
             let err_msg = format!("Failed to create sidecar command: {}", e);
-            eprintln!("❌ {}", err_msg);
+            error!("{}", err_msg);
             Err(err_msg)
         }
     }
 }
 
 fn start_websocket_listener(app: tauri::AppHandle) {
-    #[cfg(debug_assertions)]
-    println!("🔌 Starting WebSocket listener for Python backend...");
-    
+    info!("starting WebSocket listener for Python backend");
+
     tauri::async_runtime::spawn(async move {
         let mut reconnect_delay = 2u64; // Start with 2 seconds
         let max_delay = 30u64; // Cap at 30 seconds
-        
+
         loop {
             match connect_async("ws://127.0.0.1:8765/ws").await {
                 Ok((ws_stream, _)) => {
-                    #[cfg(debug_assertions)]
-                    println!("✅ Rust WebSocket connected to Python backend");
+                    info!(connection = "connected", "Rust WebSocket connected to Python backend");
                     reconnect_delay = 2; // Reset delay on successful connection
                     
                     // Emit connection state to frontend
@@ -458,8 +486,7 @@ fn start_websocket_listener(app: tauri::AppHandle) {
                             Ok(Message::Text(text)) => {
                                 if let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) {
                                     if data["type"] == "hotkey-pressed" {
-                                        #[cfg(debug_assertions)]
-                                        println!("🎯 Rust received hotkey event from Python");
+                                        debug!("received hotkey event from Python");
                                         let context = data["data"].clone();
                                         
                                         // Extract position
@@ -471,11 +498,8 @@ fn start_websocket_listener(app: tauri::AppHandle) {
                                             // Create overlay on main thread
                                             tauri::async_runtime::spawn(async move {
                                                 match show_overlay(app_clone, x, y, context).await {
-                                                    Ok(_) => {
-                                                        #[cfg(debug_assertions)]
-                                                        println!("✅ Overlay created from Rust WebSocket");
-                                                    }
-                                                    Err(e) => eprintln!("❌ Failed to create overlay: {}", e),
+                                                    Ok(_) => debug!("overlay created from Rust WebSocket"),
+                                                    Err(e) => error!("failed to create overlay: {}", e),
                                                 }
                                             });
                                         }
@@ -485,7 +509,7 @@ fn start_websocket_listener(app: tauri::AppHandle) {
                             Ok(Message::Pong(_)) => {} // Ignore pong responses
                             Ok(_) => {} // Ignore other message types
                             Err(e) => {
-                                eprintln!("❌ WebSocket error: {}", e);
+                                error!("WebSocket error: {}", e);
                                 break;
                             }
                         }
@@ -496,22 +520,20 @@ fn start_websocket_listener(app: tauri::AppHandle) {
                     
                     // Emit disconnection state
                     let _ = app.emit("backend-connection", serde_json::json!({"connected": false}));
-                    
-                    #[cfg(debug_assertions)]
-                    println!("⚠️  WebSocket connection closed, reconnecting in {}s...", reconnect_delay);
+
+                    warn!(connection = "closed", reconnect_delay, "WebSocket connection closed, reconnecting");
                     tokio::time::sleep(tokio::time::Duration::from_secs(reconnect_delay)).await;
                     
                     // Exponential backoff
                     reconnect_delay = (reconnect_delay * 2).min(max_delay);
                 }
                 Err(e) => {
-                    eprintln!("❌ Failed to connect to Python WebSocket: {}", e);
-                    
+                    error!(connection = "failed", "failed to connect to Python WebSocket: {}", e);
+
                     // Emit disconnection state
                     let _ = app.emit("backend-connection", serde_json::json!({"connected": false}));
-                    
-                    #[cfg(debug_assertions)]
-                    println!("⏳ Retrying in {}s (exponential backoff)...", reconnect_delay);
+
+                    warn!(reconnect_delay, "retrying connection (exponential backoff)");
                     tokio::time::sleep(tokio::time::Duration::from_secs(reconnect_delay)).await;
                     
                     // Exponential backoff
@@ -523,12 +545,28 @@ fn start_websocket_listener(app: tauri::AppHandle) {
 }
 
 fn main() {
+    // Install structured logging before anything else so early failures land in
+    // the log file. The guard flushes the non-blocking writer on drop, so keep
+    // it alive for the whole process.
+    let _log_guard = logging::init();
+    info!(log_path = %logging::log_path().display(), "Pointer starting");
+
+    // Local windows (overlay/response/main) all load the bundled app assets;
+    // start with an empty allowlist and opt specific (window, command) pairs back
+    // in here if a window ever needs to run a command from a remote document.
+    let ipc_allowlist = IpcAllowlist::new(Vec::<(String, String)>::new());
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(AppState {
             overlay_context: Mutex::new(None),
             response_data: Mutex::new(None),
+            last_overlay_monitor: Mutex::new(None),
         })
+        .manage(ipc_allowlist)
+        .manage(ToolCallRegistry::default())
+        .manage(ChatCancelRegistry::default())
+        .manage(ProxyServerState::default())
         .setup(|app| {
             // Create system tray menu
             let settings_item = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
@@ -560,9 +598,14 @@ fn main() {
             
             // Prevent app from quitting when main window closes
             if let Some(main_window) = app.get_webview_window("main") {
-                #[cfg(target_os = "macos")]
-                apply_macos_window_effects(&main_window);
-                
+                apply_window_chrome(&main_window);
+
+                // The main window is defined in the Tauri config, so it can't be
+                // given a builder init script; inject the drag strip directly
+                // (the document is already loaded by the time setup runs).
+                #[cfg(not(target_os = "macos"))]
+                window_chrome::inject_drag_region(&main_window);
+
                 let window_clone = main_window.clone();
                 main_window.on_window_event(move |event| {
                     if let tauri::WindowEvent::CloseRequested { api, .. } = event {
@@ -577,40 +620,60 @@ fn main() {
             std::thread::spawn(move || {
                 // Give the app a moment to fully initialize
                 std::thread::sleep(std::time::Duration::from_millis(500));
-                
-                #[cfg(debug_assertions)]
-                println!("🔄 Attempting to auto-start backend...");
+
+                debug!("attempting to auto-start backend");
                 tauri::async_runtime::block_on(async move {
                     match start_backend(app_handle.clone()).await {
                         Ok(_) => {
-                            #[cfg(debug_assertions)]
-                            println!("✅ Backend auto-started successfully");
+                            info!("backend auto-started successfully");
                             // Start WebSocket listener after backend starts
                             std::thread::sleep(std::time::Duration::from_millis(1000));
                             start_websocket_listener(app_handle);
                         },
-                        Err(e) => eprintln!("⚠️  Failed to auto-start backend: {}", e),
+                        Err(e) => warn!("failed to auto-start backend: {}", e),
                     }
                 });
             });
-            
-            println!("✅ Pointer running in menu bar");
+
+            info!("Pointer running in menu bar");
             
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            show_settings,
-            show_overlay,
-            hide_overlay,
-            get_overlay_context,
-            show_response_window,
-            get_response_data,
-            close_response_window,
-            start_backend,
-            stream_chat_cloudflare,
-            ingest_memory_cloudflare,
-            search_memory_cloudflare
-        ])
+        .invoke_handler({
+            let handler = tauri::generate_handler![
+                show_settings,
+                show_overlay,
+                hide_overlay,
+                get_overlay_context,
+                show_response_window,
+                get_response_data,
+                close_response_window,
+                get_log_path,
+                eval_in_window,
+                start_backend,
+                stream_chat_cloudflare,
+                submit_tool_results,
+                cancel_chat_cloudflare,
+                ingest_memory_cloudflare,
+                search_memory_cloudflare,
+                start_proxy_server,
+                stop_proxy_server,
+                kv_get,
+                kv_put,
+                kv_delete,
+                kv_list
+            ];
+            move |invoke| {
+                // Origin guard: reject IPC from untrusted (remote) documents
+                // before dispatching to the command body.
+                if let Err(reason) = ipc_guard::check_invoke(&invoke.message) {
+                    warn!("{}", reason);
+                    invoke.resolver.reject(reason);
+                    return true;
+                }
+                handler(invoke)
+            }
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,359 @@
+// server.rs — local OpenAI-compatible HTTP proxy for Pointer AI
+//
+// Starts a hyper/tokio listener (default 127.0.0.1:8000) exposing
+// `POST /v1/chat/completions` so other local tools (editors, CLIs) can point at
+// Pointer AI as if it were an OpenAI endpoint. Incoming OpenAI-format requests
+// are translated into the user's Cloudflare Worker calls and the Worker SSE is
+// re-emitted as standard OpenAI `chat.completion.chunk` frames (or a single
+// JSON body when `stream: false`). Before forwarding, the last user turn is run
+// through `/api/memory/search` so RAG context is injected into the prompt.
+//
+// `start_proxy_server`/`stop_proxy_server` manage the listener, with graceful
+// shutdown via a oneshot channel held in `ProxyServerState`.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+use futures_util::StreamExt;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{error, info, instrument, warn};
+
+use crate::cloudflare::{CloudflareConfig, SseDecoder};
+
+const DEFAULT_BIND: &str = "127.0.0.1:8000";
+const CHAT_COMPLETION_ID: &str = "chatcmpl-pointer";
+/// Model name echoed back when the request omits one.
+const DEFAULT_MODEL: &str = "pointer";
+
+/// Seconds since the Unix epoch, for the OpenAI `created` field. Falls back to
+/// `0` if the clock is before the epoch.
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Holds the graceful-shutdown handle of the running proxy, if any.
+#[derive(Default)]
+pub struct ProxyServerState {
+    shutdown: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+/// Start the proxy listener on `bind` (default `127.0.0.1:8000`). Returns an
+/// error if a proxy is already running.
+#[tauri::command]
+#[instrument(skip(state, config), fields(endpoint = %config.endpoint))]
+pub async fn start_proxy_server(
+    state: tauri::State<'_, ProxyServerState>,
+    config: CloudflareConfig,
+    bind: Option<String>,
+) -> Result<String, String> {
+    if state.shutdown.lock().unwrap().is_some() {
+        return Err("proxy server already running".to_string());
+    }
+
+    let addr: SocketAddr = bind
+        .unwrap_or_else(|| DEFAULT_BIND.to_string())
+        .parse()
+        .map_err(|e| format!("invalid bind address: {}", e))?;
+
+    let cfg = config.clone();
+    let make_svc = make_service_fn(move |_conn| {
+        let cfg = cfg.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, cfg.clone()))) }
+    });
+
+    let server = Server::try_bind(&addr)
+        .map_err(|e| format!("could not bind {}: {}", addr, e))?
+        .serve(make_svc);
+
+    let (tx, rx) = oneshot::channel();
+    let graceful = server.with_graceful_shutdown(async {
+        let _ = rx.await;
+    });
+
+    *state.shutdown.lock().unwrap() = Some(tx);
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = graceful.await {
+            error!("proxy server error: {}", e);
+        }
+        info!("proxy server stopped");
+    });
+
+    info!(%addr, "OpenAI-compatible proxy server started");
+    Ok(format!("proxy listening on http://{}", addr))
+}
+
+/// Stop the running proxy via its graceful-shutdown channel.
+#[tauri::command]
+#[instrument(skip(state))]
+pub fn stop_proxy_server(state: tauri::State<'_, ProxyServerState>) -> Result<String, String> {
+    match state.shutdown.lock().unwrap().take() {
+        Some(tx) => {
+            let _ = tx.send(());
+            Ok("proxy server stopping".to_string())
+        }
+        None => Err("proxy server is not running".to_string()),
+    }
+}
+
+async fn handle(req: Request<Body>, cfg: CloudflareConfig) -> Result<Response<Body>, Infallible> {
+    let response = match route(req, cfg).await {
+        Ok(resp) => resp,
+        Err((code, message)) => {
+            warn!(%code, "proxy request failed: {}", message);
+            error_response(code, &message)
+        }
+    };
+    Ok(response)
+}
+
+async fn route(
+    req: Request<Body>,
+    cfg: CloudflareConfig,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    match (req.method(), req.uri().path()) {
+        (&Method::POST, "/v1/chat/completions") => chat_completions(req, cfg).await,
+        _ => Err((StatusCode::NOT_FOUND, "not found".to_string())),
+    }
+}
+
+async fn chat_completions(
+    req: Request<Body>,
+    cfg: CloudflareConfig,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("could not read body: {}", e)))?;
+    let request: Value = serde_json::from_slice(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid JSON: {}", e)))?;
+
+    let stream = request.get("stream").and_then(|s| s.as_bool()).unwrap_or(false);
+    let model = request
+        .get("model")
+        .and_then(|m| m.as_str())
+        .unwrap_or(DEFAULT_MODEL)
+        .to_string();
+    let mut messages = request
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .cloned()
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "missing `messages`".to_string()))?;
+
+    // RAG: retrieve context for the latest user turn and inject it as a system
+    // message before forwarding to the Worker.
+    if let Some(query) = last_user_content(&messages) {
+        if let Some(context) = retrieve_context(&cfg, &query).await {
+            messages.insert(
+                0,
+                json!({
+                    "role": "system",
+                    "content": format!("Relevant context:\n{}", context),
+                }),
+            );
+        }
+    }
+
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/chat", cfg.endpoint.trim_end_matches('/'));
+    let upstream = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", cfg.api_token))
+        .header("Content-Type", "application/json")
+        .json(&json!({ "messages": messages }))
+        .send()
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("upstream request failed: {}", e)))?;
+
+    if !upstream.status().is_success() {
+        let status = upstream.status();
+        let text = upstream.text().await.unwrap_or_default();
+        return Err((
+            StatusCode::BAD_GATEWAY,
+            format!("upstream HTTP {}: {}", status, text),
+        ));
+    }
+
+    if stream {
+        Ok(stream_response(upstream, model))
+    } else {
+        json_response(upstream, model).await
+    }
+}
+
+/// Convert the Worker SSE stream into OpenAI `chat.completion.chunk` SSE frames.
+fn stream_response(upstream: reqwest::Response, model: String) -> Response<Body> {
+    let (tx, rx) = mpsc::channel::<Result<String, std::io::Error>>(32);
+    let created = unix_now();
+
+    tauri::async_runtime::spawn(async move {
+        let mut bytes = upstream.bytes_stream();
+        // Buffer across chunk boundaries so multi-byte tokens and split `data:`
+        // events survive (see `SseDecoder` in cloudflare.rs).
+        let mut decoder = SseDecoder::new();
+        while let Some(chunk) = bytes.next().await {
+            let Ok(chunk) = chunk else { break };
+            for event in decoder.feed(&chunk) {
+                let data = event.data.trim();
+                if data == "[DONE]" {
+                    let _ = tx.send(Ok(finish_frame(&model, created))).await;
+                    let _ = tx.send(Ok("data: [DONE]\n\n".to_string())).await;
+                    return;
+                }
+                if let Ok(v) = serde_json::from_str::<Value>(data) {
+                    if let Some(token) = v.get("response").and_then(|t| t.as_str()) {
+                        let _ = tx.send(Ok(delta_frame(token, &model, created))).await;
+                    }
+                }
+            }
+        }
+        // Flush a trailing event the stream closed without a blank line.
+        if let Some(event) = decoder.finish() {
+            if let Ok(v) = serde_json::from_str::<Value>(event.data.trim()) {
+                if let Some(token) = v.get("response").and_then(|t| t.as_str()) {
+                    let _ = tx.send(Ok(delta_frame(token, &model, created))).await;
+                }
+            }
+        }
+        // Upstream closed without an explicit [DONE].
+        let _ = tx.send(Ok(finish_frame(&model, created))).await;
+        let _ = tx.send(Ok("data: [DONE]\n\n".to_string())).await;
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/event-stream")
+        .header("Cache-Control", "no-cache")
+        .body(Body::wrap_stream(ReceiverStream::new(rx)))
+        .unwrap()
+}
+
+/// Accumulate the full Worker stream and return a single OpenAI `chat.completion`.
+async fn json_response(
+    upstream: reqwest::Response,
+    model: String,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    let mut content = String::new();
+    let mut bytes = upstream.bytes_stream();
+    let mut decoder = SseDecoder::new();
+    'outer: while let Some(chunk) = bytes.next().await {
+        let chunk = chunk
+            .map_err(|e| (StatusCode::BAD_GATEWAY, format!("upstream stream error: {}", e)))?;
+        for event in decoder.feed(&chunk) {
+            let data = event.data.trim();
+            if data == "[DONE]" {
+                break 'outer;
+            }
+            if let Ok(v) = serde_json::from_str::<Value>(data) {
+                if let Some(token) = v.get("response").and_then(|t| t.as_str()) {
+                    content.push_str(token);
+                }
+            }
+        }
+    }
+    // Flush a trailing event the stream closed without a blank line.
+    if let Some(event) = decoder.finish() {
+        if let Ok(v) = serde_json::from_str::<Value>(event.data.trim()) {
+            if let Some(token) = v.get("response").and_then(|t| t.as_str()) {
+                content.push_str(token);
+            }
+        }
+    }
+
+    let body = json!({
+        "id": CHAT_COMPLETION_ID,
+        "object": "chat.completion",
+        "created": unix_now(),
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop",
+        }],
+    });
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap())
+}
+
+/// Query the Worker's memory search endpoint and join the top matches into a
+/// single context string, returning `None` on any failure (RAG is best-effort).
+async fn retrieve_context(cfg: &CloudflareConfig, query: &str) -> Option<String> {
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/memory/search", cfg.endpoint.trim_end_matches('/'));
+    let resp = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", cfg.api_token))
+        .header("Content-Type", "application/json")
+        .json(&json!({ "query": query, "top_k": cfg.rag_top_k.unwrap_or(5) }))
+        .send()
+        .await
+        .ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let data: Value = resp.json().await.ok()?;
+    let joined = data
+        .get("matches")?
+        .as_array()?
+        .iter()
+        .filter_map(|m| m.get("text").and_then(|t| t.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if joined.is_empty() {
+        None
+    } else {
+        Some(joined)
+    }
+}
+
+fn last_user_content(messages: &[Value]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"))
+        .and_then(|m| m.get("content").and_then(|c| c.as_str()))
+        .map(|s| s.to_string())
+}
+
+fn delta_frame(token: &str, model: &str, created: u64) -> String {
+    let frame = json!({
+        "id": CHAT_COMPLETION_ID,
+        "object": "chat.completion.chunk",
+        "created": created,
+        "model": model,
+        "choices": [{ "index": 0, "delta": { "content": token }, "finish_reason": null }],
+    });
+    format!("data: {}\n\n", frame)
+}
+
+fn finish_frame(model: &str, created: u64) -> String {
+    let frame = json!({
+        "id": CHAT_COMPLETION_ID,
+        "object": "chat.completion.chunk",
+        "created": created,
+        "model": model,
+        "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }],
+    });
+    format!("data: {}\n\n", frame)
+}
+
+fn error_response(code: StatusCode, message: &str) -> Response<Body> {
+    let body = json!({ "error": { "message": message, "type": "proxy_error" } });
+    Response::builder()
+        .status(code)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
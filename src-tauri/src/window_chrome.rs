@@ -0,0 +1,226 @@
+// window_chrome.rs — cross-platform frameless window styling for Pointer AI
+//
+// The macOS path hides the titlebar, makes the window background transparent
+// and enables rounded corners via Cocoa. This module gives Windows and Linux
+// the same decoration-free, draggable look: a synthesized titlebar drag region
+// (forwarded to `window.start_dragging()`), plus aero-snap and a drop shadow on
+// Windows through the Win32 DWM APIs. All three window paths — the overlay, the
+// response window and the main window — go through the single
+// `apply_window_chrome` entry point regardless of platform.
+
+use tauri::WebviewWindow;
+
+/// Apply Pointer's frameless chrome to `window` on whatever platform we're on.
+///
+/// On macOS this reproduces the native titlebar-hiding + transparency effects;
+/// on Windows it adds aero-snap, a DWM drop shadow and rounded corners. On Linux
+/// it changes nothing; there the drag strip from `drag_region_init_script` is
+/// the only chrome, with no shadow or snap handling.
+pub fn apply_window_chrome(window: &WebviewWindow) {
+    #[cfg(target_os = "macos")]
+    apply_macos_window_effects(window);
+
+    #[cfg(target_os = "windows")]
+    apply_windows_window_effects(window);
+
+    // The draggable strip is installed as a builder `initialization_script` (see
+    // `drag_region_init_script`) rather than here, so it runs on every document
+    // load instead of racing a one-shot eval against the initial navigation.
+    let _ = window;
+}
+
+// ─────────────────────────── macOS ───────────────────────────────────────────
+
+#[cfg(target_os = "macos")]
+fn apply_macos_window_effects(window: &WebviewWindow) {
+    use cocoa::appkit::{NSWindow, NSWindowStyleMask, NSWindowTitleVisibility};
+    use cocoa::base::{id, nil};
+    use objc::runtime::YES;
+    use tauri::Manager;
+
+    let window_label = window.label().to_string();
+    let app_handle = window.app_handle().clone();
+
+    window.run_on_main_thread(move || {
+        if let Some(window) = app_handle.get_webview_window(&window_label) {
+            unsafe {
+                let ns_window_ptr = match window.ns_window() {
+                    Ok(ptr) => ptr,
+                    Err(e) => {
+                        eprintln!("⚠️  Could not get NSWindow for macOS effects: {}", e);
+                        return;
+                    }
+                };
+                let ns_window = ns_window_ptr as id;
+
+                // Enable rounded corners
+                ns_window.setTitlebarAppearsTransparent_(YES);
+                ns_window.setTitleVisibility_(NSWindowTitleVisibility::NSWindowTitleHidden);
+
+                let mut style_mask = ns_window.styleMask();
+                style_mask.insert(NSWindowStyleMask::NSFullSizeContentViewWindowMask);
+                ns_window.setStyleMask_(style_mask);
+
+                // CRITICAL: Make window background transparent to avoid black corners
+                let _: () = msg_send![ns_window, setOpaque: 0];
+                let clear_color: id = msg_send![class!(NSColor), clearColor];
+                let _: () = msg_send![ns_window, setBackgroundColor: clear_color];
+
+                // Also make sure the content view background is transparent
+                let content_view: id = ns_window.contentView();
+                let _: () = msg_send![content_view, setWantsLayer: 1];
+                let layer: id = msg_send![content_view, layer];
+                if !layer.is_null() {
+                    let _: () = msg_send![layer, setBackgroundColor: nil];
+                }
+            }
+        }
+    })
+    .ok();
+}
+
+// ─────────────────────────── Windows ─────────────────────────────────────────
+
+#[cfg(target_os = "windows")]
+fn apply_windows_window_effects(window: &WebviewWindow) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Dwm::{
+        DwmExtendFrameIntoClientArea, DwmSetWindowAttribute, DWMWA_WINDOW_CORNER_PREFERENCE,
+        DWMWCP_ROUND,
+    };
+    use windows::Win32::UI::Controls::MARGINS;
+    use windows::Win32::UI::Shell::SetWindowSubclass;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetWindowLongPtrW, GWL_STYLE, WS_CAPTION, WS_MAXIMIZEBOX, WS_THICKFRAME,
+    };
+
+    let hwnd = match window.hwnd() {
+        Ok(h) => HWND(h.0),
+        Err(e) => {
+            eprintln!("⚠️  Could not get HWND for Windows effects: {}", e);
+            return;
+        }
+    };
+
+    unsafe {
+        // Re-assert the caption/thick-frame style bits so the DWM keeps driving
+        // aero-snap and snap-assist even though the frame is painted away below.
+        let style = GetWindowLongPtrW(hwnd, GWL_STYLE);
+        let style = style | (WS_CAPTION.0 | WS_THICKFRAME.0 | WS_MAXIMIZEBOX.0) as isize;
+        SetWindowLongPtrW(hwnd, GWL_STYLE, style);
+
+        // Subclass the window to zero the non-client margins in WM_NCCALCSIZE;
+        // without this the caption bits re-asserted above make Windows repaint
+        // the native titlebar and border, undoing the frameless look. The
+        // subclass keeps the whole window as client area while the style bits
+        // preserve snap behaviour.
+        let _ = SetWindowSubclass(hwnd, Some(frameless_subclass_proc), FRAMELESS_SUBCLASS_ID, 0);
+
+        // Extend the frame into the client area by 1px so the DWM draws its drop
+        // shadow for us without giving back a visible titlebar.
+        let margins = MARGINS {
+            cxLeftWidth: 1,
+            cxRightWidth: 1,
+            cyTopHeight: 1,
+            cyBottomHeight: 1,
+        };
+        let _ = DwmExtendFrameIntoClientArea(hwnd, &margins);
+
+        // Rounded corners to match the macOS look on Windows 11.
+        let pref = DWMWCP_ROUND;
+        let _ = DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_WINDOW_CORNER_PREFERENCE,
+            &pref as *const _ as *const _,
+            std::mem::size_of_val(&pref) as u32,
+        );
+    }
+}
+
+/// Subclass id for the frameless non-client handler (arbitrary, unique per HWND).
+#[cfg(target_os = "windows")]
+const FRAMELESS_SUBCLASS_ID: usize = 0x504F_494E; // 'POIN'
+
+/// Window subclass procedure that removes the non-client area. Handling
+/// `WM_NCCALCSIZE` (with `wParam == TRUE`) by returning `0` without shrinking the
+/// proposed client rectangle makes the entire window client area, so the native
+/// caption bar and border are never drawn; all other messages fall through to
+/// the default handler.
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn frameless_subclass_proc(
+    hwnd: windows::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+    _subclass_id: usize,
+    _ref_data: usize,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::LRESULT;
+    use windows::Win32::UI::Shell::DefSubclassProc;
+    use windows::Win32::UI::WindowsAndMessaging::WM_NCCALCSIZE;
+
+    if msg == WM_NCCALCSIZE && wparam.0 != 0 {
+        return LRESULT(0);
+    }
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+}
+
+// ─────────────────────────── Drag region ─────────────────────────────────────
+
+/// A thin draggable strip across the top of the webview that forwards presses to
+/// `window.start_dragging()` (via the Tauri drag IPC), so frameless windows can
+/// still be moved on platforms with no native titlebar left.
+///
+/// Installed as a `WebviewWindowBuilder::initialization_script`, so it re-runs on
+/// every document load; it runs at document start, so the actual insertion is
+/// deferred to `DOMContentLoaded` when `document.body` is guaranteed to exist.
+#[cfg(not(target_os = "macos"))]
+const DRAG_REGION_JS: &str = r#"
+    (function () {
+        function install() {
+            if (document.getElementById('__pointer_drag_region')) return;
+            const bar = document.createElement('div');
+            bar.id = '__pointer_drag_region';
+            bar.style.cssText =
+                'position:fixed;top:0;left:0;right:0;height:28px;z-index:2147483647;' +
+                '-webkit-app-region:drag;app-region:drag;';
+            bar.addEventListener('mousedown', function (e) {
+                if (e.button !== 0) return;
+                if (window.__TAURI__ && window.__TAURI__.window) {
+                    window.__TAURI__.window.getCurrentWindow().startDragging();
+                }
+            });
+            document.body.appendChild(bar);
+        }
+        if (document.readyState === 'loading') {
+            document.addEventListener('DOMContentLoaded', install);
+        } else {
+            install();
+        }
+    })();
+"#;
+
+/// The initialization script that installs the frameless drag strip, to be
+/// passed to every `WebviewWindowBuilder::initialization_script`. Empty on macOS,
+/// where the native full-size content view already handles dragging.
+pub fn drag_region_init_script() -> &'static str {
+    #[cfg(not(target_os = "macos"))]
+    {
+        DRAG_REGION_JS
+    }
+    #[cfg(target_os = "macos")]
+    {
+        ""
+    }
+}
+
+/// Install the drag strip into an already-created window, for config-defined
+/// windows (the main window) that cannot be given a builder initialization
+/// script. The script is idempotent and `DOMContentLoaded`-gated, so evaluating
+/// it against the already-loaded document installs the strip exactly once.
+#[cfg(not(target_os = "macos"))]
+pub fn inject_drag_region(window: &WebviewWindow) {
+    if let Err(e) = window.eval(DRAG_REGION_JS) {
+        eprintln!("⚠️  Could not inject drag region: {}", e);
+    }
+}